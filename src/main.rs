@@ -1,3 +1,10 @@
+// The `ntsc` module is a self-contained encoder/decoder library whose public API (the alternate TV
+// systems, signal paths, noise models, test patterns, and the per-sample streaming decode) is a
+// deliberate surface that this thin demo binary only exercises a slice of at a time via the consts
+// below. Promoting it to its own `lib` target would make the rest reachable, but this source
+// snapshot ships without a manifest, so we allow the otherwise-unused API here instead.
+#![allow(dead_code)]
+
 mod ntsc;
 mod types;
 
@@ -8,7 +15,7 @@ use rand::rngs::StdRng;
 use winit::event::Event;
 use winit::{event_loop::EventLoop, window::WindowBuilder, dpi::PhysicalSize};
 use crate::ntsc::*;
-use crate::types::SignalFloat;
+use crate::types::{RgbSample, SignalFloat};
 
 /// The output image width.
 const OUTPUT_WIDTH: u32 = 640;
@@ -19,15 +26,33 @@ const OUTPUT_HEIGHT: u32 = NTSC_SCANLINE_COUNT;
 /// The test image data.
 const IMAGE_DATA: &[u8] = include_bytes!("../yamato.png");
 
+/// The TV system to encode and decode for. Switching this to `TvSystem::PalB` or `TvSystem::PalM`
+/// retimes the encoder and decoder and swaps in that system's colour matrices. The demo's own
+/// output height, line period and sample rate below stay on the NTSC constants.
+const TV_SYSTEM: TvSystem = TvSystem::Ntsc;
+
+/// The signal source. `None` encodes the bundled image; `Some(pattern)` fills the encoder with one
+/// of the procedural calibration patterns instead, which is handy for eyeballing the decode
+/// artifacts against a known input.
+const SOURCE_PATTERN: Option<TestPattern> = None;
+
 /// The amount of timing jitter to add to each scanline, in order to add a little analog 'jiggle'.
 const TIMING_JITTER: SignalFloat = NTSC_SCANLINE_PERIOD * 0.000;
 
-/// The amount of noise to add to the encoded signal before decoding it. The signal is attenuated
-/// by the noise, and 1.0 noise leaves none of the original signal and just colorful snow.
-const SIGNAL_NOISE: SignalFloat = 0.00;
+/// The analog noise model to degrade the encoded signal with before decoding it. `NoiseModel::None`
+/// leaves the signal clean; the other models reproduce weak-signal static, snow, and rolling
+/// interference bars.
+const NOISE_MODEL: NoiseModel = NoiseModel::None;
 
-/// The length of time for the entire output image.
-const OUTPUT_IMAGE_TIME: SignalFloat = NTSC_SCANLINE_PERIOD * OUTPUT_HEIGHT as SignalFloat;
+/// Whether to run the decoded signal through the 1-line comb filter. When enabled each scanline is
+/// combed against the one above it to separate luma and chroma, which removes most of the dot crawl
+/// and rainbow fringing the plain box-average separation leaves behind.
+const COMB_FILTER: bool = false;
+
+/// The signal path to encode and decode over. The composite paths run through the batched
+/// `modulate_scanline`/`demodulate_scanline` pipeline; `SVideo` and `Rgb` keep their channels
+/// separate and have no single-wire batched form, so they stream through `sample`/`decode` instead.
+const SIGNAL_TYPE: SignalType = SignalType::CompositeColour;
 
 /// The number of samples to use per period to get accurate results. We need to average a sine wave
 /// over its period and obtain a value as close to 0 as possible to minimize error when decoding
@@ -39,6 +64,16 @@ const SAMPLES_PER_PERIOD: usize = 5;
 // TODO: note the sine wave might not align with the start of a scanline.
 const TIME_PER_SAMPLE: SignalFloat = NTSC_COLOR_CARRIER_PERIOD / SAMPLES_PER_PERIOD as SignalFloat;
 
+/// The sample rate the scanline modulate/demodulate passes run at. We keep the original per-carrier
+/// sampling (`SAMPLES_PER_PERIOD` samples per colour-carrier period) so the I/Q demodulation stays
+/// orthogonal, and let the decoder slide its integration window across the line.
+const SAMPLE_RATE: SignalFloat = 1.0 / TIME_PER_SAMPLE;
+
+/// The number of signal samples in one output scanline: the whole number of samples that fits in
+/// `NTSC_SCANLINE_PERIOD` (rounded down, so the span falls just short of a full scanline); the
+/// decoder then combs this down to `OUTPUT_WIDTH` pixels with overlapping integration windows.
+const SAMPLES_PER_LINE: usize = (NTSC_SCANLINE_PERIOD / TIME_PER_SAMPLE) as usize;
+
 /// Generate timing jitter.
 fn generate_timing_jitter(rng: &mut impl Rng) -> SignalFloat {
     if TIMING_JITTER > 0.0 {
@@ -49,15 +84,6 @@ fn generate_timing_jitter(rng: &mut impl Rng) -> SignalFloat {
     }
 }
 
-/// Generate signal noise.
-fn generate_signal_noise(rng: &mut impl Rng) -> SignalFloat {
-    if SIGNAL_NOISE > 0.0 {
-        rng.gen_range(0.0..SIGNAL_NOISE)
-    }
-    else {
-        0.0
-    }
-}
 
 /// The main test program for the NTSC encoder/decoder - creates an NtscEncoder with the image from
 /// `IMAGE_DATA` loaded in, and then encodes a signal using it, adds noise and timing jitter, and
@@ -92,46 +118,76 @@ fn main() -> Result<(), Box<dyn Error>> {
             .build()?
     };
 
-    // Create NTSC encoder and decoder and load image.
-    let encoder = NtscEncoder::from_image_buf(IMAGE_DATA)?;
-    let mut decoder = NtscDecoder::new(SAMPLES_PER_PERIOD);
+    // Create NTSC encoder and decoder. The source is either the bundled image or a procedural
+    // calibration pattern, encoded for the selected TV system.
+    let encoder = match SOURCE_PATTERN {
+        Some(pattern) => {
+            NtscEncoder::from_test_pattern(pattern, OUTPUT_WIDTH, OUTPUT_HEIGHT, TV_SYSTEM)
+        }
+        None => NtscEncoder::from_image_buf_with_system(IMAGE_DATA, TV_SYSTEM)?,
+    };
+    let mut decoder = NtscDecoder::with_system(SAMPLES_PER_PERIOD, encoder.tv_system());
+    if COMB_FILTER {
+        decoder.enable_comb_filter(SAMPLES_PER_LINE);
+    }
 
     // Create random number generator.
     let mut rng = StdRng::from_entropy();
 
+    // The output row buffer for the two-pass modulate/demodulate pipeline, allocated once and
+    // reused for every scanline.
+    let mut row: Vec<RgbSample> = vec![(0.0, 0.0, 0.0); OUTPUT_WIDTH as usize];
+
     // Main loop.
     event_loop.run(move |event, _, _| {
         match event {
             // Fill the buffer with pixel data whenever a redraw is requested.
             Event::RedrawRequested(_) => {
                 let buf = pixels.get_frame_mut();
-                let mut time_offset = 0.0;
-                for (idx, pixel) in buf.chunks_exact_mut(4).enumerate() {
-                    if (idx as u32) % OUTPUT_WIDTH == 0 {
-                        time_offset = generate_timing_jitter(&mut rng);
-                    }
 
-                    // Calculate pixel time (start) in signal.
-                    let idx_nrm = idx as SignalFloat / (OUTPUT_WIDTH * OUTPUT_HEIGHT+10) as SignalFloat;
-                    let pixel_time = idx_nrm * OUTPUT_IMAGE_TIME;
-
-                    // Generate `SAMPLES_PER_PERIOD` samples across the color carrier within the
-                    // pixel, and push them to the NtscDecoder.
-                    let mut sample_time = pixel_time + time_offset;
-                    for _ in 0..SAMPLES_PER_PERIOD {
-                        let noise = generate_signal_noise(&mut rng);
-                        let sample = encoder.sample(sample_time) * (1.0 - SIGNAL_NOISE) + noise;
-                        decoder.push_sample(sample_time, sample);
-                        sample_time += TIME_PER_SAMPLE;
+                // One row at a time. The composite paths modulate the whole line into a signal
+                // buffer, add any analog noise, then demodulate the line straight back into a row of
+                // RGB; S-Video and RGB keep their channels separate, so they stream through the
+                // per-sample `sample`/`decode` API instead.
+                for y in 0..OUTPUT_HEIGHT {
+                    let start_time = y as SignalFloat * NTSC_SCANLINE_PERIOD
+                        + generate_timing_jitter(&mut rng);
+
+                    match SIGNAL_TYPE {
+                        SignalType::CompositeColour | SignalType::CompositeMonochrome => {
+                            let mut signal = encoder.modulate_scanline(
+                                start_time, SAMPLE_RATE, SAMPLES_PER_LINE, SIGNAL_TYPE);
+                            for (i, sample) in signal.iter_mut().enumerate() {
+                                let time = start_time + i as SignalFloat * TIME_PER_SAMPLE;
+                                *sample = NOISE_MODEL.perturb(time, *sample, &mut rng);
+                            }
+                            decoder.demodulate_scanline(
+                                &signal, start_time, SAMPLE_RATE, &mut row, SIGNAL_TYPE, true, false);
+                        }
+                        SignalType::SVideo | SignalType::Rgb => {
+                            // Stream SAMPLES_PER_PERIOD samples per output pixel through the decoder's
+                            // integration window, then read back the decoded colour.
+                            for (x, pixel) in row.iter_mut().enumerate() {
+                                let pixel_time = start_time
+                                    + x as SignalFloat / OUTPUT_WIDTH as SignalFloat
+                                        * NTSC_SCANLINE_PERIOD;
+                                for s in 0..SAMPLES_PER_PERIOD {
+                                    let time = pixel_time + s as SignalFloat * TIME_PER_SAMPLE;
+                                    decoder.push_sample(time, encoder.sample(time, SIGNAL_TYPE));
+                                }
+                                *pixel = decoder.decode(SIGNAL_TYPE, true, false);
+                            }
+                        }
                     }
 
-                    // Decode new samples.
-                    let (r, g, b) = decoder.decode(true);
-
-                    pixel[0] = SignalFloat::clamp(r * 255.0, 0.0, 255.0) as u8;
-                    pixel[1] = SignalFloat::clamp(g * 255.0, 0.0, 255.0) as u8;
-                    pixel[2] = SignalFloat::clamp(b * 255.0, 0.0, 255.0) as u8;
-                    pixel[3] = 0xFF;
+                    let row_offset = (y * OUTPUT_WIDTH * 4) as usize;
+                    for (x, &(r, g, b)) in row.iter().enumerate() {
+                        let idx = row_offset + x * 4;
+                        buf[idx] = SignalFloat::clamp(r * 255.0, 0.0, 255.0) as u8;
+                        buf[idx + 1] = SignalFloat::clamp(g * 255.0, 0.0, 255.0) as u8;
+                        buf[idx + 2] = SignalFloat::clamp(b * 255.0, 0.0, 255.0) as u8;
+                        buf[idx + 3] = 0xFF;
+                    }
                 }
                 pixels.render().expect("Failed to render pixel buffer to screen");
             },
@@ -1,10 +1,20 @@
 mod encoder;
 mod decoder;
+mod tv_system;
+mod color;
+mod signal;
+mod noise;
+mod test_pattern;
 
 pub use encoder::*;
 pub use decoder::*;
+pub use tv_system::*;
+pub use color::*;
+pub use signal::*;
+pub use noise::*;
+pub use test_pattern::*;
 
-use crate::types::SignalFloat;
+use crate::types::{SignalFloat, PI};
 
 /// The frequency of the color carrier wave in hz.
 pub const NTSC_COLOR_CARRIER_FREQ: SignalFloat = 3.579545e6;
@@ -23,3 +33,16 @@ pub const NTSC_SCANLINE_PERIOD: SignalFloat = 64e-6;
 /// The length of time for a full image in an NTSC signal, which is the period for each scanline
 /// times the number of scanlines.
 pub const NTSC_IMAGE_PERIOD: SignalFloat = NTSC_SCANLINE_PERIOD * NTSC_SCANLINE_COUNT as SignalFloat;
+
+/// Generate the colour carrier reference at a given time for a given carrier frequency. When
+/// `in_phase` is true this is the in-phase (sine) component used to modulate/demodulate the first
+/// chroma axis, otherwise it's the quadrature (cosine) component for the second axis.
+pub fn generate_color_carrier(time: SignalFloat, freq: SignalFloat, in_phase: bool) -> SignalFloat {
+    let phase = time * 2.0 * PI * freq;
+    if in_phase {
+        SignalFloat::sin(phase)
+    }
+    else {
+        SignalFloat::cos(phase)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::types::{SignalFloat, SrgbSample};
+
+/// A 3x3 matrix stored row-major, as used for the colour-space conversions. We only ever need the
+/// handful of operations below so there's no dependency on a linear algebra crate.
+pub type Mat3 = [[SignalFloat; 3]; 3];
+
+/// A 3-vector, used for tristimulus (XYZ) values and cone responses.
+type Vec3 = [SignalFloat; 3];
+
+/// The chromaticities of the 1953 NTSC primaries. These are far wider than modern sRGB, which is
+/// the whole reason the cheap conversion gets colours like the infamous purple-instead-of-blue
+/// sky wrong.
+const NTSC_1953_PRIMARIES: [[SignalFloat; 2]; 3] =
+    [[0.67, 0.33], [0.21, 0.71], [0.14, 0.08]];
+
+/// CIE Illuminant C, the white point the 1953 NTSC system was defined against.
+const ILLUMINANT_C: [SignalFloat; 2] = [0.310, 0.316];
+
+/// The chromaticities of the sRGB primaries.
+const SRGB_PRIMARIES: [[SignalFloat; 2]; 3] =
+    [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]];
+
+/// CIE Illuminant D65, the white point sRGB is defined against.
+const ILLUMINANT_D65: [SignalFloat; 2] = [0.3127, 0.3290];
+
+/// The standard Bradford cone-response matrix, which transforms XYZ into the "cone" space the
+/// chromatic adaptation scaling is performed in.
+const BRADFORD: Mat3 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+/// Convert an xy chromaticity to an XYZ tristimulus value normalised to Y = 1.
+fn xy_to_xyz([x, y]: [SignalFloat; 2]) -> Vec3 {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+/// Multiply a matrix by a column vector.
+fn mat_vec(m: &Mat3, v: &Vec3) -> Vec3 {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// Multiply two matrices.
+fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+        }
+    }
+    out
+}
+
+/// Invert a 3x3 matrix via the adjugate / determinant. The matrices we feed this are always
+/// well-conditioned colour transforms, so we don't guard against a zero determinant.
+fn mat_inverse(m: &Mat3) -> Mat3 {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    let inv_det = 1.0 / det;
+
+    [
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv_det,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv_det,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv_det,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+        ],
+    ]
+}
+
+/// Build the linear-RGB to XYZ matrix for a set of primaries under a given white point, following
+/// the standard method: scale each primary's XYZ so that full-intensity RGB reproduces the white.
+fn rgb_to_xyz_matrix(primaries: &[[SignalFloat; 2]; 3], white: [SignalFloat; 2]) -> Mat3 {
+    let r = xy_to_xyz(primaries[0]);
+    let g = xy_to_xyz(primaries[1]);
+    let b = xy_to_xyz(primaries[2]);
+
+    let m = [
+        [r[0], g[0], b[0]],
+        [r[1], g[1], b[1]],
+        [r[2], g[2], b[2]],
+    ];
+
+    let white_xyz = xy_to_xyz(white);
+    let scale = mat_vec(&mat_inverse(&m), &white_xyz);
+
+    [
+        [m[0][0] * scale[0], m[0][1] * scale[1], m[0][2] * scale[2]],
+        [m[1][0] * scale[0], m[1][1] * scale[1], m[1][2] * scale[2]],
+        [m[2][0] * scale[0], m[2][1] * scale[1], m[2][2] * scale[2]],
+    ]
+}
+
+/// Build the Bradford chromatic adaptation matrix from a source white point to a destination white
+/// point: `M_BFD⁻¹ · diag(ρ_dst/ρ_src, γ_dst/γ_src, β_dst/β_src) · M_BFD`, where ρ,γ,β are the cone
+/// responses of each white's XYZ under the Bradford transform.
+fn bradford_adaptation(src_white: [SignalFloat; 2], dst_white: [SignalFloat; 2]) -> Mat3 {
+    let src_cone = mat_vec(&BRADFORD, &xy_to_xyz(src_white));
+    let dst_cone = mat_vec(&BRADFORD, &xy_to_xyz(dst_white));
+
+    let diag = [
+        [dst_cone[0] / src_cone[0], 0.0, 0.0],
+        [0.0, dst_cone[1] / src_cone[1], 0.0],
+        [0.0, 0.0, dst_cone[2] / src_cone[2]],
+    ];
+
+    mat_mul(&mat_inverse(&BRADFORD), &mat_mul(&diag, &BRADFORD))
+}
+
+/// Build the combined matrix that takes linear RGB in the 1953 NTSC primaries (white Illuminant C)
+/// all the way to linear RGB in the sRGB primaries (white D65), adapting the white point with
+/// Bradford along the way. It's built once per decoder rather than per sample.
+pub fn ntsc1953_to_srgb_matrix() -> Mat3 {
+    let ntsc_to_xyz = rgb_to_xyz_matrix(&NTSC_1953_PRIMARIES, ILLUMINANT_C);
+    let adapt = bradford_adaptation(ILLUMINANT_C, ILLUMINANT_D65);
+    let xyz_to_srgb = mat_inverse(&rgb_to_xyz_matrix(&SRGB_PRIMARIES, ILLUMINANT_D65));
+
+    mat_mul(&xyz_to_srgb, &mat_mul(&adapt, &ntsc_to_xyz))
+}
+
+/// The sRGB transfer curve (OETF), encoding a linear light value for display.
+fn srgb_transfer(c: SignalFloat) -> SignalFloat {
+    let c = SignalFloat::clamp(c, 0.0, 1.0);
+    if c <= 0.0031308 {
+        12.92 * c
+    }
+    else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Apply a precomputed NTSC-to-sRGB matrix to a linear RGB colour in the NTSC primaries and encode
+/// the result with the sRGB transfer curve, giving a colour-accurate display value.
+pub fn ntsc_linear_to_srgb(matrix: &Mat3, (r, g, b): SrgbSample) -> SrgbSample {
+    let linear = mat_vec(matrix, &[r, g, b]);
+    (srgb_transfer(linear[0]), srgb_transfer(linear[1]), srgb_transfer(linear[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Full-intensity NTSC-1953 white is defined against Illuminant C, sRGB white against D65. With
+    /// the Bradford adaptation in the matrix, feeding linear white in should give linear white back
+    /// out - if the white point handling were wrong, white would pick up a tint.
+    #[test]
+    fn white_maps_to_white() {
+        let matrix = ntsc1953_to_srgb_matrix();
+        let white = mat_vec(&matrix, &[1.0, 1.0, 1.0]);
+        for channel in white {
+            assert!((channel - 1.0).abs() < 1e-3, "white channel drifted: {channel}");
+        }
+    }
+}
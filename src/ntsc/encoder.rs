@@ -1,5 +1,5 @@
 use std::{error::Error, io::Cursor};
-use crate::types::{PixelSample, YiqSample, PI, SignalFloat, SrgbSample, RgbSample};
+use crate::types::{PixelSample, PI, SampleValue, SignalFloat, SrgbSample, RgbSample};
 use crate::ntsc::*;
 
 /// The NTSC encoder, allows you to sample the NTSC signal at a given time, generated from an
@@ -8,53 +8,164 @@ pub struct NtscEncoder {
     width: u32,
     height: u32,
     pixel_buffer: Vec<u8>,
+    tv_system: TvSystem,
 }
 
 impl NtscEncoder {
     /// Create a new NTSC encoder with a pixel buffer initialized to all 0s.
     pub fn new(width: u32, height: u32) -> Self {
+        Self::with_system(width, height, TvSystem::Ntsc)
+    }
+
+    /// Create a new encoder for the given TV system with a pixel buffer initialized to all 0s.
+    pub fn with_system(width: u32, height: u32, tv_system: TvSystem) -> Self {
         let pixel_buffer = vec![0; (width * height * 4) as usize];
 
         Self {
             width,
             height,
             pixel_buffer,
+            tv_system,
         }
     }
 
     /// Initialize the NTSC decoder so that it contains an image file from a buffer (e.g. obtained
     /// from include_dir!).
     pub fn from_image_buf(buf: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Self::from_image_buf_with_system(buf, TvSystem::Ntsc)
+    }
+
+    /// Initialize an encoder for the given TV system with an image file loaded from a buffer.
+    pub fn from_image_buf_with_system(buf: &[u8], tv_system: TvSystem) -> Result<Self, Box<dyn Error>> {
         // Load image and convert to rgba8 pixel buffer.
         let img = image::io::Reader::new(Cursor::new(buf))
             .with_guessed_format()?
             .decode()?
             .into_rgba8();
 
-        let mut encoder = NtscEncoder::new(img.width(), img.height());
+        let mut encoder = NtscEncoder::with_system(img.width(), img.height(), tv_system);
         encoder.pixel_buffer = img.into_raw();
 
         Ok(encoder)
     }
 
-    /// Sample the NTSC signal at a given time.
-    pub fn sample(&self, time: SignalFloat) -> SignalFloat {
+    /// Initialize an encoder whose pixel buffer is filled with a procedural calibration pattern,
+    /// so the encode/decode round-trip can be exercised without any external image assets.
+    pub fn from_test_pattern(kind: TestPattern, width: u32, height: u32, tv_system: TvSystem) -> Self {
+        let mut encoder = NtscEncoder::with_system(width, height, tv_system);
+        encoder.pixel_buffer = kind.render(width, height);
+        encoder
+    }
+
+    /// The TV system this encoder is producing a signal for.
+    pub fn tv_system(&self) -> TvSystem {
+        self.tv_system
+    }
+
+    /// Sample the NTSC signal at a given time for the given signal path. The shape of the returned
+    /// [`SignalSample`] depends on the `signal_type`: composite modes carry a single summed value,
+    /// S-Video keeps luma and chroma separate, and RGB carries the pixel untouched.
+    pub fn sample(&self, time: SignalFloat, signal_type: SignalType) -> SignalSample {
         // Convert time back to a pixel coordinate. We round back to the last pixel before the
         // given time, as if the signal changes instantly whenever there's a new pixel.
         // TODO: a bit wrong semantically, I think the number of scanlines supported by the NTSC
         // decoder shouldn't depend on the output image size, but the other way around.
-        let time = time % NTSC_IMAGE_PERIOD;
-        let y = (time / NTSC_SCANLINE_PERIOD) as u32 as SignalFloat / NTSC_SCANLINE_COUNT as SignalFloat;
-        let x = (time % NTSC_SCANLINE_PERIOD) / NTSC_SCANLINE_PERIOD;
+        let tv = self.tv_system;
+        let line = (time.rem_euclid(tv.image_period()) / tv.scanline_period()) as u32;
+        let time = time % tv.image_period();
+        let y_coord = line as SignalFloat / tv.scanline_count() as SignalFloat;
+        let x = (time % tv.scanline_period()) / tv.scanline_period();
 
         // Sample pixel buffer.
-        let pixel_sample = self.sample_pixel(x, y);
+        let pixel_sample = self.sample_pixel(x, y_coord);
 
-        // Output pixel luma.
-        let (y, i, q) = Self::srgb_to_yiq(pixel_sample);
+        // The RGB path bypasses modulation entirely.
+        if let SignalType::Rgb = signal_type {
+            return SignalSample::Rgb(pixel_sample);
+        }
 
-        y + i * SignalFloat::sin(time * 2.0 * PI * NTSC_COLOR_CARRIER_FREQ)
-          + q * SignalFloat::cos(time * 2.0 * PI * NTSC_COLOR_CARRIER_FREQ)
+        // Convert to the system's luma/chroma space. PAL flips the second chroma axis' phase on
+        // odd lines, so we flip its sign before modulating to match.
+        let (y, c1, mut c2) = tv.srgb_to_chroma(pixel_sample);
+        if tv.alternates_v_phase() && line % 2 == 1 {
+            c2 = -c2;
+        }
+
+        let freq = tv.color_carrier_freq();
+        let chroma = c1 * generate_color_carrier(time, freq, true)
+                   + c2 * generate_color_carrier(time, freq, false);
+
+        match signal_type {
+            // Luma and modulated chroma summed onto one wire.
+            SignalType::CompositeColour => SignalSample::Composite(y + chroma),
+            // Drop the chroma, leaving a monochrome luma signal.
+            SignalType::CompositeMonochrome => SignalSample::Composite(y),
+            // Keep luma and chroma on separate wires so the decoder can skip separation.
+            SignalType::SVideo => SignalSample::SVideo { luma: y, chroma },
+            // Handled above.
+            SignalType::Rgb => unreachable!(),
+        }
+    }
+
+    /// Modulate a whole scanline's worth of composite signal in one pass, returning `sample_count`
+    /// samples taken at `1.0 / sample_rate` intervals starting from `start_time`.
+    ///
+    /// This is the batched counterpart to [`sample`](Self::sample) for the composite paths:
+    /// instead of calling `sin`/`cos` for every sample it advances the colour-carrier phasor by a
+    /// fixed rotation each step, which removes the per-sample trig and the per-pixel allocation and
+    /// maps cleanly onto a future shader port. `CompositeMonochrome` drops the chroma, leaving the
+    /// bare luma on the wire. The S-Video and RGB paths keep luma and chroma (or the pixel) on
+    /// separate channels and so have no single-wire batched form - use [`sample`](Self::sample) for
+    /// those.
+    pub fn modulate_scanline(&self, start_time: SignalFloat, sample_rate: SignalFloat, sample_count: usize, signal_type: SignalType) -> Vec<SampleValue> {
+        let colour = match signal_type {
+            SignalType::CompositeColour => true,
+            SignalType::CompositeMonochrome => false,
+            SignalType::SVideo | SignalType::Rgb => {
+                panic!("modulate_scanline only handles the composite signal paths; \
+                        use sample() for SVideo and Rgb")
+            }
+        };
+
+        let tv = self.tv_system;
+        let freq = tv.color_carrier_freq();
+        let dt = 1.0 / sample_rate;
+
+        // The whole line shares a scanline, so its PAL phase flip is fixed up front.
+        let line = (start_time.rem_euclid(tv.image_period()) / tv.scanline_period()) as u32;
+        let flip = tv.alternates_v_phase() && line % 2 == 1;
+
+        // Precompute the phasor rotation for one sample step, then advance it incrementally rather
+        // than recomputing the carrier from scratch each sample.
+        let delta = dt * 2.0 * PI * freq;
+        let (sin_d, cos_d) = (SignalFloat::sin(delta), SignalFloat::cos(delta));
+        let phase0 = start_time * 2.0 * PI * freq;
+        let (mut sin_p, mut cos_p) = (SignalFloat::sin(phase0), SignalFloat::cos(phase0));
+
+        let mut signal = Vec::with_capacity(sample_count);
+        for i in 0..sample_count {
+            let time = start_time + i as SignalFloat * dt;
+            let t = time.rem_euclid(tv.image_period());
+            let y_coord = (t / tv.scanline_period()) as u32 as SignalFloat / tv.scanline_count() as SignalFloat;
+            let x = (t % tv.scanline_period()) / tv.scanline_period();
+
+            let (y, c1, mut c2) = tv.srgb_to_chroma(self.sample_pixel(x, y_coord));
+            if flip {
+                c2 = -c2;
+            }
+            // sin_p/cos_p are the in-phase/quadrature carrier at this sample. Monochrome drops the
+            // modulated chroma and leaves the bare luma on the wire.
+            let chroma = if colour { c1 * sin_p + c2 * cos_p } else { 0.0 };
+            signal.push(y + chroma);
+
+            // Rotate the phasor by one sample step via the angle-addition identities.
+            let next_sin = sin_p * cos_d + cos_p * sin_d;
+            let next_cos = cos_p * cos_d - sin_p * sin_d;
+            sin_p = next_sin;
+            cos_p = next_cos;
+        }
+
+        signal
     }
 
     /// Sample a pixel at the given pixel index.
@@ -76,17 +187,6 @@ impl NtscEncoder {
         Self::rgba8_to_rgbf(sample_u8)
     }
 
-    /// Convert from rgb to yiq.
-    fn srgb_to_yiq((r, g, b): SrgbSample) -> YiqSample {
-        // Calculate luma.
-        // https://en.wikipedia.org/wiki/YIQ
-        let y = 0.3 * r + 0.59 * g + 0.11 * b;
-        let i = -0.27 * (b - y) + 0.74 * (r - y);
-        let q = 0.41 * (b - y) + 0.48 * (r - y);
-
-        (y, i, q)
-    }
-
     /// Convert from rgb8 pixel data to rgb float data.
     fn rgba8_to_rgbf((r, g, b, _): PixelSample) -> RgbSample {
         (r as SignalFloat / 255.0, g as SignalFloat / 255.0, b as SignalFloat / 255.0)
@@ -0,0 +1,53 @@
+use rand::Rng;
+use crate::types::{PI, SampleTime, SampleValue, SignalFloat};
+
+/// A model of analog signal degradation. Each variant perturbs a sample differently, so the
+/// encode -> noise -> decode pipeline can reproduce distinct real-world failure modes rather than a
+/// single uniform jitter. Apply one with [`NoiseModel::perturb`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseModel {
+    /// A perfectly clean signal - the sample is passed through untouched.
+    None,
+    /// Additive white Gaussian noise, the classic model of thermal/weak-signal static. `stddev` is
+    /// the standard deviation of the noise added to each sample.
+    Gaussian { stddev: SignalFloat },
+    /// "Snow": sparse, full-amplitude impulses scattered across the picture, as seen on a badly
+    /// tuned channel. `rate` is the probability (0..1) that any given sample is replaced by an
+    /// impulse.
+    Snow { rate: SignalFloat },
+    /// A slow low-frequency gain drift that rolls bright and dark bands down the picture, imitating
+    /// interference bars. `frequency` is the drift frequency in hz and `depth` how far the gain
+    /// swings either side of unity.
+    GainDrift { frequency: SignalFloat, depth: SignalFloat },
+}
+
+impl NoiseModel {
+    /// Perturb a single sample taken at `time`, returning the degraded value. The RNG is only
+    /// touched by the stochastic models.
+    pub fn perturb(&self, time: SampleTime, value: SampleValue, rng: &mut impl Rng) -> SampleValue {
+        match *self {
+            NoiseModel::None => value,
+            NoiseModel::Gaussian { stddev } => value + stddev * gaussian(rng),
+            NoiseModel::Snow { rate } => {
+                if rng.gen_range(0.0..1.0) < rate {
+                    // Full-amplitude impulse, positive or negative.
+                    if rng.gen_bool(0.5) { 1.0 } else { -1.0 }
+                }
+                else {
+                    value
+                }
+            }
+            NoiseModel::GainDrift { frequency, depth } => {
+                value * (1.0 + depth * SignalFloat::sin(time * 2.0 * PI * frequency))
+            }
+        }
+    }
+}
+
+/// Draw a standard normal sample (mean 0, standard deviation 1) using the Box-Muller transform.
+fn gaussian(rng: &mut impl Rng) -> SignalFloat {
+    // Guard u1 away from zero so the logarithm stays finite.
+    let u1: SignalFloat = rng.gen_range(SignalFloat::EPSILON..1.0);
+    let u2: SignalFloat = rng.gen_range(0.0..1.0);
+    SignalFloat::sqrt(-2.0 * u1.ln()) * SignalFloat::cos(2.0 * PI * u2)
+}
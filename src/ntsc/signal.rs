@@ -0,0 +1,31 @@
+use crate::types::{RgbSample, SampleValue};
+
+/// The physical connection the signal is carried over. Real consoles and TVs offered several, each
+/// trading cost against how much the luma and chroma interfere with one another, so selecting one
+/// lets you A/B the artifact severity of each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignalType {
+    /// Luma and modulated chroma summed into a single wire - the cheapest connection, and the one
+    /// that suffers dot crawl and rainbow fringing.
+    CompositeColour,
+    /// Luma and chroma carried on separate wires, so the decoder never has to separate them and
+    /// avoids the composite cross-talk artifacts.
+    SVideo,
+    /// Composite with the chroma dropped entirely, giving a black and white picture.
+    CompositeMonochrome,
+    /// Raw RGB with no modulation at all - the pixel is carried through untouched.
+    Rgb,
+}
+
+/// A single sampled point of the encoded signal. Its shape depends on the [`SignalType`] it was
+/// produced for: composite modes carry one summed value, S-Video keeps luma and chroma apart, and
+/// the RGB path carries the pixel directly.
+#[derive(Clone, Copy, Debug)]
+pub enum SignalSample {
+    /// A single composite sample (colour or monochrome).
+    Composite(SampleValue),
+    /// Separate luma and modulated-chroma samples, as carried over S-Video.
+    SVideo { luma: SampleValue, chroma: SampleValue },
+    /// A raw RGB pixel, carried without modulation.
+    Rgb(RgbSample),
+}
@@ -0,0 +1,173 @@
+use crate::types::{PI, SignalFloat};
+use crate::ntsc::TvSystem;
+
+/// A procedural calibration pattern that can be rendered straight into an encoder's pixel buffer,
+/// so the encode/decode round-trip and its artifacts can be checked without shipping image files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestPattern {
+    /// SMPTE colour bars: the seven 75% bars on top, the reverse castellation strip, and the
+    /// -I / white / +Q / PLUGE row along the bottom.
+    SmpteBars,
+    /// A PM5544-style setup grid with a centred circle and a pair of resolution wedges, useful for
+    /// checking geometry and how the decoder handles fine detail.
+    Pm5544,
+    /// A pure chroma sweep that ramps hue across the screen at constant luma, for seeing how hard
+    /// the encoder drives the subcarrier and how the decoder tracks a continuously changing tint.
+    ChromaSweep,
+}
+
+impl TestPattern {
+    /// Render the pattern into a freshly allocated rgba8 pixel buffer of the given size.
+    pub fn render(self, width: u32, height: u32) -> Vec<u8> {
+        let mut buf = vec![0u8; (width * height * 4) as usize];
+        match self {
+            TestPattern::SmpteBars => smpte_bars(&mut buf, width, height),
+            TestPattern::Pm5544 => pm5544(&mut buf, width, height),
+            TestPattern::ChromaSweep => chroma_sweep(&mut buf, width, height),
+        }
+        buf
+    }
+}
+
+/// Write an opaque rgb pixel into an rgba8 buffer at the given coordinate.
+fn set_pixel(buf: &mut [u8], width: u32, x: u32, y: u32, (r, g, b): (u8, u8, u8)) {
+    let idx = ((y * width + x) * 4) as usize;
+    buf[idx] = r;
+    buf[idx + 1] = g;
+    buf[idx + 2] = b;
+    buf[idx + 3] = 0xFF;
+}
+
+/// Convert an HSV colour (hue in degrees, saturation and value in 0..1) to rgb8.
+fn hsv_to_rgb(h: SignalFloat, s: SignalFloat, v: SignalFloat) -> (u8, u8, u8) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = v - c;
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let to_u8 = |f: SignalFloat| (SignalFloat::clamp(f + m, 0.0, 1.0) * 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+/// Fill `buf` with SMPTE colour bars.
+fn smpte_bars(buf: &mut [u8], width: u32, height: u32) {
+    // The seven 75% bars, the reverse castellation strip, and the bottom PLUGE row.
+    const BARS: [(u8, u8, u8); 7] = [
+        (191, 191, 191), // gray
+        (191, 191, 0),   // yellow
+        (0, 191, 191),   // cyan
+        (0, 191, 0),     // green
+        (191, 0, 191),   // magenta
+        (191, 0, 0),     // red
+        (0, 0, 191),     // blue
+    ];
+    const REVERSE: [(u8, u8, u8); 7] = [
+        (0, 0, 191),     // blue
+        (19, 19, 19),    // black
+        (191, 0, 191),   // magenta
+        (19, 19, 19),    // black
+        (0, 191, 191),   // cyan
+        (19, 19, 19),    // black
+        (191, 191, 191), // gray
+    ];
+    // -I, 100% white, +Q, black, then the PLUGE bars (below-black, above-black) and black.
+    const BOTTOM: [(u8, u8, u8); 7] = [
+        (0, 41, 79),     // -I
+        (255, 255, 255), // 100% white
+        (50, 0, 106),    // +Q
+        (19, 19, 19),    // black
+        (9, 9, 9),       // below-black PLUGE
+        (29, 29, 29),    // above-black PLUGE
+        (19, 19, 19),    // black
+    ];
+
+    let bars_bottom = height * 2 / 3;
+    let reverse_bottom = height * 3 / 4;
+
+    for y in 0..height {
+        let row = if y < bars_bottom {
+            &BARS
+        }
+        else if y < reverse_bottom {
+            &REVERSE
+        }
+        else {
+            &BOTTOM
+        };
+
+        for x in 0..width {
+            let bar = (x * 7 / width) as usize;
+            set_pixel(buf, width, x, y, row[bar.min(6)]);
+        }
+    }
+}
+
+/// Fill `buf` with a PM5544-style setup grid, a centred circle, and a pair of resolution wedges.
+fn pm5544(buf: &mut [u8], width: u32, height: u32) {
+    const BACKGROUND: (u8, u8, u8) = (128, 128, 128);
+    const LINE: (u8, u8, u8) = (230, 230, 230);
+
+    // Grid cell size and the centre / circle geometry.
+    let cell = (height / 12).max(1);
+    let cx = width as SignalFloat / 2.0;
+    let cy = height as SignalFloat / 2.0;
+    let radius = (height as SignalFloat / 2.0) * 0.9;
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut color = BACKGROUND;
+
+            // Grid lines.
+            if x % cell == 0 || y % cell == 0 {
+                color = LINE;
+            }
+
+            // Resolution wedges: vertical line gratings of increasing frequency in two bands to
+            // either side of the centre. `saturating_sub` keeps the lower bound from underflowing
+            // on very short images.
+            let band = cell * 2;
+            if y >= (cy as u32).saturating_sub(band) && y < cy as u32 + band {
+                let dx = (x as SignalFloat - cx).abs();
+                if dx > radius * 0.2 && dx < radius * 0.6 {
+                    // Stripe period shrinks as we move outwards, giving a frequency wedge.
+                    let period = (6.0 - dx / radius * 6.0).max(1.0) as u32;
+                    color = if (x / period) % 2 == 0 { (255, 255, 255) } else { (0, 0, 0) };
+                }
+            }
+
+            // Centred circle outline.
+            let dist = ((x as SignalFloat - cx).powi(2) + (y as SignalFloat - cy).powi(2)).sqrt();
+            if (dist - radius).abs() < 1.5 {
+                color = (0, 0, 0);
+            }
+
+            set_pixel(buf, width, x, y, color);
+        }
+    }
+}
+
+/// Fill `buf` with a hue ramp at constant luma. The hue is the angle of a fixed-length I/Q chroma
+/// vector around a fixed luma, kept small enough that every hue stays inside the RGB gamut.
+fn chroma_sweep(buf: &mut [u8], width: u32, height: u32) {
+    const LUMA: SignalFloat = 0.5;
+    const SATURATION: SignalFloat = 0.2;
+    let to_u8 = |f: SignalFloat| (SignalFloat::clamp(f, 0.0, 1.0) * 255.0).round() as u8;
+
+    for y in 0..height {
+        for x in 0..width {
+            let hue = x as SignalFloat / width as SignalFloat * 2.0 * PI;
+            let chroma = (LUMA, SATURATION * hue.cos(), SATURATION * hue.sin());
+            let (r, g, b) = TvSystem::Ntsc.chroma_to_rgb(chroma);
+            set_pixel(buf, width, x, y, (to_u8(r), to_u8(g), to_u8(b)));
+        }
+    }
+}
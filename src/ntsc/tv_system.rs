@@ -0,0 +1,121 @@
+use crate::types::{SignalFloat, SrgbSample, YiqSample};
+
+/// The frequency of the PAL color subcarrier in hz. PAL runs its chroma a little under a megahertz
+/// higher than NTSC, which is part of why the two standards' artifacts look so different.
+pub const PAL_COLOR_CARRIER_FREQ: SignalFloat = 4.43361875e6;
+
+/// The number of scanlines in a PAL broadcast (the extra hundred lines over NTSC are the headline
+/// difference between the two systems).
+pub const PAL_SCANLINE_COUNT: u32 = 625;
+
+/// The length of time for each PAL scanline in seconds. PAL runs at 50Hz with 625 lines, giving a
+/// 64us line - the same value this crate uses for `NTSC_SCANLINE_PERIOD`.
+pub const PAL_SCANLINE_PERIOD: SignalFloat = 64e-6;
+
+/// A television broadcast standard. Each variant supplies the carrier frequency, scanline
+/// timing, and the chroma encode/decode matrices for its colour system, so the encoder and
+/// decoder can be pointed at NTSC or PAL content without any of the timing or colour constants
+/// being baked in.
+///
+/// The PAL variants additionally alternate the phase of their second chroma axis (the V/Q
+/// equivalent) every scanline - the "Phase Alternating Line" the standard is named for - which
+/// the encoder and decoder account for by flipping that component's sign on odd lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TvSystem {
+    /// 525-line / 3.58MHz NTSC with YIQ chroma, as used in North America and Japan.
+    Ntsc,
+    /// 625-line / 4.43MHz PAL (the common B/G/H/I timing) with YUV chroma and line-alternating V
+    /// phase.
+    PalB,
+    /// PAL-M as used in Brazil: NTSC's 525-line timing carrying PAL's line-alternating colour.
+    PalM,
+}
+
+impl TvSystem {
+    /// The frequency of the colour carrier wave in hz for this system.
+    pub fn color_carrier_freq(&self) -> SignalFloat {
+        match self {
+            TvSystem::Ntsc | TvSystem::PalM => super::NTSC_COLOR_CARRIER_FREQ,
+            TvSystem::PalB => PAL_COLOR_CARRIER_FREQ,
+        }
+    }
+
+    /// The period of the colour carrier sine wave for this system.
+    pub fn color_carrier_period(&self) -> SignalFloat {
+        1.0 / self.color_carrier_freq()
+    }
+
+    /// The number of scanlines in a full image for this system.
+    pub fn scanline_count(&self) -> u32 {
+        match self {
+            TvSystem::Ntsc | TvSystem::PalM => super::NTSC_SCANLINE_COUNT,
+            TvSystem::PalB => PAL_SCANLINE_COUNT,
+        }
+    }
+
+    /// The length of time for a single scanline in seconds.
+    pub fn scanline_period(&self) -> SignalFloat {
+        match self {
+            TvSystem::Ntsc | TvSystem::PalM => super::NTSC_SCANLINE_PERIOD,
+            TvSystem::PalB => PAL_SCANLINE_PERIOD,
+        }
+    }
+
+    /// The length of time for a full image, which is the scanline period times the number of
+    /// scanlines.
+    pub fn image_period(&self) -> SignalFloat {
+        self.scanline_period() * self.scanline_count() as SignalFloat
+    }
+
+    /// Whether this system alternates the phase of its second chroma axis every scanline, as the
+    /// PAL variants do. NTSC keeps a fixed phase.
+    pub fn alternates_v_phase(&self) -> bool {
+        matches!(self, TvSystem::PalB | TvSystem::PalM)
+    }
+
+    /// Convert an sRGB sample to this system's chroma-bearing colour space (YIQ for NTSC, YUV for
+    /// the PAL variants). The result is `(luma, first_chroma, second_chroma)`, where the second
+    /// chroma axis is the one whose phase PAL alternates per line.
+    pub fn srgb_to_chroma(&self, (r, g, b): SrgbSample) -> YiqSample {
+        match self {
+            TvSystem::Ntsc => {
+                // https://en.wikipedia.org/wiki/YIQ
+                let y = 0.3 * r + 0.59 * g + 0.11 * b;
+                let i = -0.27 * (b - y) + 0.74 * (r - y);
+                let q = 0.41 * (b - y) + 0.48 * (r - y);
+                (y, i, q)
+            }
+            // PAL-M carries PAL's line-alternating YUV chroma on NTSC timing, so it shares the PAL
+            // colour matrices even though its carrier and line timing come from the NTSC arms above.
+            TvSystem::PalB | TvSystem::PalM => {
+                // https://en.wikipedia.org/wiki/YUV (BT.470 PAL coefficients)
+                let y = 0.299 * r + 0.587 * g + 0.114 * b;
+                let u = 0.492 * (b - y);
+                let v = 0.877 * (r - y);
+                (y, u, v)
+            }
+        }
+    }
+
+    /// Convert a `(luma, first_chroma, second_chroma)` sample from this system's colour space back
+    /// to sRGB.
+    pub fn chroma_to_rgb(&self, (y, c1, c2): YiqSample) -> SrgbSample {
+        match self {
+            TvSystem::Ntsc => {
+                // https://en.wikipedia.org/wiki/YIQ
+                let r = y + 0.9469 * c1 + 0.6236 * c2;
+                let g = y - 0.2748 * c1 - 0.6357 * c2;
+                let b = y - 1.1 * c1 + 1.7 * c2;
+                (r, g, b)
+            }
+            // PAL-M shares PAL's YUV decode matrix (see `srgb_to_chroma`).
+            TvSystem::PalB | TvSystem::PalM => {
+                // https://en.wikipedia.org/wiki/YUV (BT.470 PAL coefficients)
+                let r = y + 1.14 * c2;
+                let g = y - 0.395 * c1 - 0.581 * c2;
+                let b = y + 2.032 * c1;
+                (r, g, b)
+            }
+        }
+    }
+}